@@ -1,30 +1,59 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt,
     hash::{Hash, Hasher},
     ops::Deref,
     rc::Rc,
 };
 
-use crate::macro_cell::{MacroCell, MacroCellBranch};
+use crate::macro_cell::{content_hash_branch, MacroCell, MacroCellBranch};
+use crate::rule::Rule;
 
+/// A [`MacroCellBranch`], hash-consed so that structurally identical
+/// subtrees share one `Rc`. Carries its precomputed content hash alongside
+/// the `Rc` so lookups and re-hashing never need to walk the tree.
 #[derive(Clone)]
-pub struct CachedMacroCellBranch(Rc<MacroCellBranch>);
+pub struct CachedMacroCellBranch(Rc<MacroCellBranch>, u64);
 
 impl CachedMacroCellBranch {
-    pub fn new_result(branch: MacroCellBranch, cache: &mut Cache) -> (Self, MacroCell) {
-        if let Some((key, result)) = cache.result.get_key_value(&branch) {
-            (Self(key.clone()), result.clone())
+    /// Hash-conses `branch`: if a structurally identical branch already
+    /// exists in `cache`, returns a clone of its `Rc`; otherwise inserts
+    /// `branch` and returns a handle to it. Lookup is by content hash, with
+    /// a full structural comparison against same-hash candidates as the
+    /// collision fallback.
+    pub fn intern(branch: MacroCellBranch, cache: &mut Cache) -> Self {
+        let hash = content_hash_branch(&branch);
+        let bucket = cache.nodes.entry(hash).or_default();
+        if let Some(rc) = bucket.iter().find(|rc| rc.branches == branch.branches) {
+            Self(Rc::clone(rc), hash)
         } else {
-            let result = branch.compute_result(cache);
             let rc = Rc::new(branch);
-            cache.result.insert(Rc::clone(&rc), result.clone());
-            (Self(rc), result)
+            bucket.push(Rc::clone(&rc));
+            Self(rc, hash)
         }
     }
 
-    pub fn result(&self, cache: &Cache) -> MacroCell {
-        cache.result[&self.0].clone()
+    pub fn content_hash(&self) -> u64 {
+        self.1
+    }
+
+    pub fn new_result(branch: MacroCellBranch, step_pow: u32, cache: &mut Cache) -> (Self, MacroCell) {
+        let cached = Self::intern(branch, cache);
+        let result = cached.result(cache, step_pow);
+        (cached, result)
+    }
+
+    pub fn result(&self, cache: &mut Cache, step_pow: u32) -> MacroCell {
+        if let Some(result) = cache.result.get(&step_pow).and_then(|by_step| by_step.get(&self.0)) {
+            return result.clone();
+        }
+        let result = self.0.compute_result(cache, step_pow);
+        cache
+            .result
+            .entry(step_pow)
+            .or_default()
+            .insert(Rc::clone(&self.0), result.clone());
+        result
     }
 }
 
@@ -46,7 +75,7 @@ impl Eq for CachedMacroCellBranch {}
 
 impl Hash for CachedMacroCellBranch {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        Rc::as_ptr(&self.0).hash(state)
+        self.1.hash(state)
     }
 }
 
@@ -59,13 +88,205 @@ impl Deref for CachedMacroCellBranch {
 }
 
 pub struct Cache {
-    result: HashMap<Rc<MacroCellBranch>, MacroCell>,
+    rule: Rule,
+    nodes: HashMap<u64, Vec<Rc<MacroCellBranch>>>,
+    result: HashMap<u32, HashMap<Rc<MacroCellBranch>, MacroCell>>,
+    node_budget: Option<usize>,
 }
 
 impl Cache {
-    pub fn new() -> Self {
+    /// Memoized results are only valid for the `Rule` the cache was built
+    /// with; switching rules means building a new `Cache`.
+    pub fn new(rule: Rule) -> Self {
         Self {
+            rule,
+            nodes: HashMap::new(),
             result: HashMap::new(),
+            node_budget: None,
+        }
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Number of distinct branches currently interned in this cache.
+    pub fn len(&self) -> usize {
+        self.nodes.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The node budget set by [`Cache::set_node_budget`], if any.
+    pub fn capacity(&self) -> Option<usize> {
+        self.node_budget
+    }
+
+    /// Sets (or clears, with `None`) the node budget used by
+    /// [`Cache::gc_if_over_budget`] to decide when to collect automatically.
+    pub fn set_node_budget(&mut self, budget: Option<usize>) {
+        self.node_budget = budget;
+    }
+
+    /// Runs [`Cache::gc`] if [`Cache::len`] exceeds the budget set via
+    /// [`Cache::set_node_budget`]. Returns whether a collection happened.
+    pub fn gc_if_over_budget(&mut self, roots: &[&MacroCell]) -> bool {
+        match self.node_budget {
+            Some(budget) if self.len() > budget => {
+                self.gc(roots);
+                true
+            }
+            _ => false,
         }
     }
+
+    /// Mark-and-sweep collection: keeps only the branches and memoized
+    /// results reachable from `roots`, dropping everything else. Any
+    /// `MacroCell::Branch` handle not reachable from `roots` becomes invalid
+    /// to use against this cache afterward (looking up its result will
+    /// recompute it from scratch as if it were new; the handle itself is not
+    /// interned again).
+    pub fn gc(&mut self, roots: &[&MacroCell]) {
+        let mut marked = HashSet::new();
+        for root in roots {
+            mark_reachable(root, &mut marked);
+        }
+
+        for bucket in self.nodes.values_mut() {
+            bucket.retain(|rc| marked.contains(&Rc::as_ptr(rc)));
+        }
+        self.nodes.retain(|_, bucket| !bucket.is_empty());
+
+        for by_step in self.result.values_mut() {
+            by_step.retain(|rc, _| marked.contains(&Rc::as_ptr(rc)));
+        }
+    }
+}
+
+fn mark_reachable(cell: &MacroCell, marked: &mut HashSet<*const MacroCellBranch>) {
+    if let MacroCell::Branch(branch) = cell {
+        if marked.insert(Rc::as_ptr(&branch.0)) {
+            for row in &branch.branches {
+                for child in row {
+                    mark_reachable(child, marked);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_buffer::parse_plaintext;
+
+    #[test]
+    fn gc_drops_unreachable_nodes_and_keeps_the_retained_root_queryable() {
+        let mut cache = Cache::new(Rule::default());
+        let world_buf = parse_plaintext(
+            "\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                .........OO.....\n\
+                ........O.O.....\n\
+                ........OO......\n\
+                ......OO........\n\
+                .....O.O........\n\
+                .....OO.........\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+            ",
+        );
+        let world = MacroCell::from_square(world_buf.view(), &mut cache);
+        let step_pow = world.level() - 2;
+        let result = world.result(&mut cache, step_pow).unwrap();
+
+        // Computing `result` interns a bunch of shifted/cropped helper
+        // branches that aren't part of `result`'s own subtree; only keeping
+        // `result` as a root should let gc drop those.
+        let before = cache.len();
+        cache.gc(&[&result]);
+        let after = cache.len();
+        assert!(
+            after < before,
+            "gc should drop branches unreachable from the retained root"
+        );
+        assert!(after > 0, "result's own branches should survive gc");
+
+        // The retained root must still produce a correct result after gc,
+        // re-interning whatever it needs along the way. `CachedMacroCellBranch`'s
+        // `PartialEq` compares by `Rc` pointer identity, so comparing against
+        // a result built in a separate cache has to go through `StateBuffer`
+        // rather than `assert_eq!` directly.
+        let next_step_pow = result.level() - 2;
+        let recomputed = result.result(&mut cache, next_step_pow).unwrap();
+
+        let mut fresh_cache = Cache::new(Rule::default());
+        let fresh_world = MacroCell::from_square(world_buf.view(), &mut fresh_cache);
+        let fresh_result = fresh_world.result(&mut fresh_cache, step_pow).unwrap();
+        let expected = fresh_result.result(&mut fresh_cache, next_step_pow).unwrap();
+
+        let recomputed_buf = recomputed.to_state_buffer();
+        let expected_buf = expected.to_state_buffer();
+        assert_eq!(recomputed_buf.view().rows(), expected_buf.view().rows());
+        assert_eq!(recomputed_buf.view().cols(), expected_buf.view().cols());
+        for row in 0..recomputed_buf.view().rows() {
+            for col in 0..recomputed_buf.view().cols() {
+                assert_eq!(recomputed_buf.view()[(row, col)], expected_buf.view()[(row, col)]);
+            }
+        }
+    }
+
+    #[test]
+    fn gc_if_over_budget_only_collects_once_the_budget_is_exceeded() {
+        let mut cache = Cache::new(Rule::default());
+        let world_buf = parse_plaintext(
+            "\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                .........OO.....\n\
+                ........O.O.....\n\
+                ........OO......\n\
+                ......OO........\n\
+                .....O.O........\n\
+                .....OO.........\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+            ",
+        );
+        let world = MacroCell::from_square(world_buf.view(), &mut cache);
+        let step_pow = world.level() - 2;
+        let result = world.result(&mut cache, step_pow).unwrap();
+
+        // No budget set: gc_if_over_budget never collects.
+        assert_eq!(cache.capacity(), None);
+        assert!(!cache.gc_if_over_budget(&[&result]));
+
+        // Budget set above the current size: still under budget, no-op.
+        let before = cache.len();
+        cache.set_node_budget(Some(before + 1));
+        assert_eq!(cache.capacity(), Some(before + 1));
+        assert!(!cache.gc_if_over_budget(&[&result]));
+        assert_eq!(cache.len(), before);
+
+        // Budget set below the current size: collects, dropping anything
+        // unreachable from the given roots.
+        cache.set_node_budget(Some(before - 1));
+        assert!(cache.gc_if_over_budget(&[&result]));
+        assert!(cache.len() < before);
+    }
 }
@@ -0,0 +1,5 @@
+pub mod cache;
+pub mod macro_cell;
+pub mod render;
+pub mod rule;
+pub mod state_buffer;
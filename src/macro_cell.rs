@@ -1,117 +1,7 @@
-use std::ops::{Bound, Index, Range, RangeBounds};
+use std::hash::{Hash, Hasher};
 
 use crate::cache::{Cache, CachedMacroCellBranch};
-
-pub struct StateBuffer {
-    rows: usize,
-    cols: usize,
-    state: Vec<bool>,
-}
-
-impl StateBuffer {
-    pub fn new(state: Vec<bool>, rows: usize, cols: usize) -> Self {
-        Self { rows, cols, state }
-    }
-
-    pub fn view(&self) -> StateBufferView {
-        StateBufferView::new(&self.state, self.rows, self.cols)
-    }
-}
-
-#[derive(Clone, Copy)]
-pub struct StateBufferView<'a> {
-    rows: usize,
-    cols: usize,
-    row_stride: usize,
-    view: &'a [bool],
-}
-
-fn normalize_range<R: RangeBounds<usize>>(range: R, start: usize, end: usize) -> Range<usize> {
-    let norm_start = match range.start_bound() {
-        Bound::Included(&i) => i,
-        Bound::Excluded(&i) => i + 1,
-        Bound::Unbounded => start,
-    };
-    let norm_end = match range.end_bound() {
-        Bound::Included(&i) => i + 1,
-        Bound::Excluded(&i) => i,
-        Bound::Unbounded => end,
-    };
-    assert!(norm_start >= start && norm_end <= end && norm_start <= norm_end);
-    norm_start..norm_end
-}
-
-impl<'a> StateBufferView<'a> {
-    pub fn new(buffer: &'a [bool], rows: usize, cols: usize) -> Self {
-        assert_eq!(buffer.len(), rows * cols);
-        Self {
-            rows,
-            cols,
-            row_stride: cols,
-            view: buffer,
-        }
-    }
-
-    pub fn sub_rectangle<R: RangeBounds<usize>, C: RangeBounds<usize>>(
-        &self,
-        rows: R,
-        cols: C,
-    ) -> Self {
-        let rows = normalize_range(rows, 0, self.rows);
-        let cols = normalize_range(cols, 0, self.cols);
-        Self {
-            rows: rows.end - rows.start,
-            cols: cols.end - cols.start,
-            row_stride: self.row_stride,
-            view: &self.view[rows.start * self.row_stride + cols.start..],
-        }
-    }
-
-    pub fn rows(&self) -> usize {
-        self.rows
-    }
-
-    pub fn cols(&self) -> usize {
-        self.cols
-    }
-}
-
-impl<'a> Index<(usize, usize)> for StateBufferView<'a> {
-    type Output = bool;
-
-    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
-        assert!(row < self.rows && col < self.cols);
-        &self.view[row * self.row_stride + col]
-    }
-}
-
-impl<'a, const ROWS: usize, const COLS: usize> From<&'a [[bool; ROWS]; COLS]>
-    for StateBufferView<'a>
-{
-    fn from(value: &'a [[bool; ROWS]; COLS]) -> Self {
-        Self::new(value.flatten(), ROWS, COLS)
-    }
-}
-
-pub fn parse_plaintext(s: &str) -> StateBuffer {
-    let lines = s.lines().filter(|line| !line.starts_with('!'));
-    let rows = lines.clone().count();
-    let cols = lines.clone().map(|line| line.len()).max().unwrap();
-    let mut buf = vec![false; rows * cols];
-
-    for (i, line) in lines.enumerate() {
-        for (j, c) in line.as_bytes().iter().enumerate() {
-            let state = match c {
-                b'.' => false,
-                b'O' => true,
-                _ => panic!("unexpected char {:?}", c),
-            };
-            buf[i * cols + j] = state;
-        }
-    }
-
-    StateBuffer::new(buf, rows, cols)
-}
+use crate::state_buffer::{StateBuffer, StateBufferView};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum MacroCell {
@@ -122,14 +12,12 @@ pub enum MacroCell {
 impl MacroCell {
     pub fn from_square(square: StateBufferView, cache: &mut Cache) -> Self {
         assert!(square.rows() == square.cols());
-        assert!(square.rows().is_power_of_two() && square.rows >= 2);
+        assert!(square.rows().is_power_of_two() && square.rows() >= 2);
         if square.rows() == 2 {
-            Self::Leaf(MacroCellLeaf {
-                states: [
-                    [square[(0, 0)], square[(0, 1)]],
-                    [square[(1, 0)], square[(1, 1)]],
-                ],
-            })
+            Self::Leaf(MacroCellLeaf::new([
+                [square[(0, 0)], square[(0, 1)]],
+                [square[(1, 0)], square[(1, 1)]],
+            ]))
         } else {
             let cut = square.rows() / 2;
             let branch = MacroCellBranch {
@@ -144,15 +32,57 @@ impl MacroCell {
                     ],
                 ],
             };
-            let (branch, _result) = CachedMacroCellBranch::new_result(branch, cache);
+            let branch = CachedMacroCellBranch::intern(branch, cache);
             Self::Branch(branch)
         }
     }
 
-    pub fn result(&self, cache: &Cache) -> Option<MacroCell> {
+    pub fn result(&self, cache: &mut Cache, step_pow: u32) -> Option<MacroCell> {
         match self {
             Self::Leaf(..) => None,
-            Self::Branch(branch) => Some(branch.result(cache)),
+            Self::Branch(branch) => Some(branch.result(cache, step_pow)),
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        match self {
+            Self::Leaf(..) => 1,
+            Self::Branch(branch) => branch.level(),
+        }
+    }
+
+    pub(crate) fn content_hash(&self) -> u64 {
+        match self {
+            Self::Leaf(leaf) => leaf.content_hash(),
+            Self::Branch(branch) => branch.content_hash(),
+        }
+    }
+
+    /// Recursively renders this node into a dense `StateBuffer` of side
+    /// `2^self.level()`.
+    pub fn to_state_buffer(&self) -> StateBuffer {
+        let side = 1usize << self.level();
+        let mut state = vec![false; side * side];
+        self.write_into(&mut state, side, 0, 0);
+        StateBuffer::new(state, side, side)
+    }
+
+    fn write_into(&self, buf: &mut [bool], stride: usize, row: usize, col: usize) {
+        match self {
+            Self::Leaf(leaf) => {
+                for i in 0..2 {
+                    for j in 0..2 {
+                        buf[(row + i) * stride + (col + j)] = leaf.states[i][j];
+                    }
+                }
+            }
+            Self::Branch(branch) => {
+                let half = 1usize << (branch.level() - 1);
+                branch.branches[0][0].write_into(buf, stride, row, col);
+                branch.branches[0][1].write_into(buf, stride, row, col + half);
+                branch.branches[1][0].write_into(buf, stride, row + half, col);
+                branch.branches[1][1].write_into(buf, stride, row + half, col + half);
+            }
         }
     }
 }
@@ -169,9 +99,41 @@ impl From<CachedMacroCellBranch> for MacroCell {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy)]
 pub struct MacroCellLeaf {
     pub states: [[bool; 2]; 2],
+    content_hash: u64,
+}
+
+impl MacroCellLeaf {
+    pub fn new(states: [[bool; 2]; 2]) -> Self {
+        let bits = (states[0][0] as u64) << 3
+            | (states[0][1] as u64) << 2
+            | (states[1][0] as u64) << 1
+            | (states[1][1] as u64);
+        Self {
+            states,
+            content_hash: splitmix64(bits),
+        }
+    }
+
+    pub(crate) fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+}
+
+impl PartialEq for MacroCellLeaf {
+    fn eq(&self, other: &Self) -> bool {
+        self.states == other.states
+    }
+}
+
+impl Eq for MacroCellLeaf {}
+
+impl Hash for MacroCellLeaf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.content_hash.hash(state)
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -179,6 +141,75 @@ pub struct MacroCellBranch {
     pub branches: [[MacroCell; 2]; 2],
 }
 
+/// The classic splitmix64 finalizer, used to mix a [`MacroCellLeaf`]'s
+/// packed states or a branch quadrant's content hash into a well-distributed
+/// `u64`, so nodes can be hash-consed without rehashing their subtrees.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Folds the content hashes of `branch`'s four quadrants into a single
+/// `u64`, rotating each quadrant's hash by a distinct amount so that e.g.
+/// swapping top-left and top-right content doesn't collide.
+pub(crate) fn content_hash_branch(branch: &MacroCellBranch) -> u64 {
+    let [[tl, tr], [bl, br]] = &branch.branches;
+    splitmix64(tl.content_hash())
+        ^ splitmix64(tr.content_hash()).rotate_left(16)
+        ^ splitmix64(bl.content_hash()).rotate_left(32)
+        ^ splitmix64(br.content_hash()).rotate_left(48)
+}
+
+/// Crops the center quadrant, one level down, out of four same-level
+/// sibling nodes arranged as `[[top_left, top_right], [bottom_left,
+/// bottom_right]]` -- taking the innermost cell or grandchild from each,
+/// without advancing any generations. Used both to combine the nine
+/// overlapping shifted subnodes in [`MacroCellBranch::compute_result`]'s
+/// slow path, and (in tests) to crop a faster-jump result down to the same
+/// level as repeated single-generation steps for comparison.
+pub(crate) fn crop_quadrant(
+    top_left: &MacroCell,
+    top_right: &MacroCell,
+    bottom_left: &MacroCell,
+    bottom_right: &MacroCell,
+    cache: &mut Cache,
+) -> MacroCell {
+    match (top_left, top_right, bottom_left, bottom_right) {
+        (
+            MacroCell::Leaf(top_left),
+            MacroCell::Leaf(top_right),
+            MacroCell::Leaf(bottom_left),
+            MacroCell::Leaf(bottom_right),
+        ) => MacroCell::Leaf(MacroCellLeaf::new([
+            [top_left.states[1][1], top_right.states[1][0]],
+            [bottom_left.states[0][1], bottom_right.states[0][0]],
+        ])),
+        (
+            MacroCell::Branch(top_left),
+            MacroCell::Branch(top_right),
+            MacroCell::Branch(bottom_left),
+            MacroCell::Branch(bottom_right),
+        ) => {
+            let branch = MacroCellBranch {
+                branches: [
+                    [
+                        top_left.branches[1][1].clone(),
+                        top_right.branches[1][0].clone(),
+                    ],
+                    [
+                        bottom_left.branches[0][1].clone(),
+                        bottom_right.branches[0][0].clone(),
+                    ],
+                ],
+            };
+            MacroCell::Branch(CachedMacroCellBranch::intern(branch, cache))
+        }
+        _ => unreachable!("mismatched levels among sibling macro cells"),
+    }
+}
+
 impl MacroCellBranch {
     pub fn map_branches<T, F, G>(&self, leaf_map: F, branch_map: G) -> T
     where
@@ -198,9 +229,24 @@ impl MacroCellBranch {
         }
     }
 
-    pub fn compute_result(&self, cache: &mut Cache) -> MacroCell {
+    pub fn level(&self) -> u32 {
+        self.branches[0][0].level() + 1
+    }
+
+    /// Advances this node by exactly `2^step_pow` generations, for any
+    /// `step_pow <= self.level() - 2`. `step_pow == self.level() - 2` is the
+    /// maximum jump HashLife can take in one recursive step, and reuses the
+    /// nine-overlapping-subresults construction directly. Anything slower
+    /// takes the same nine overlapping subnodes of `self` (the four children
+    /// and the five shifted quadrants, all at level `n-1`) *without*
+    /// advancing them, recurses on each at the same `step_pow` to do the
+    /// advancing, then crops the nine results down to the four final
+    /// quadrants without advancing any further.
+    pub fn compute_result(&self, cache: &mut Cache, step_pow: u32) -> MacroCell {
+        let rule = cache.rule();
         self.map_branches(
             |leaves: [[MacroCellLeaf; 2]; 2]| -> MacroCell {
+                assert_eq!(step_pow, 0, "a branch of leaves can only advance by a single generation");
                 let mut states = [[false; 4]; 4];
                 for i in 0..4 {
                     for j in 0..4 {
@@ -226,122 +272,212 @@ impl MacroCellBranch {
                             .filter(|(di, dj)| states[i + di][j + dj])
                             .count();
                         let self_state = states[i + 1][j + 1];
-                        let next_state = match (self_state, alive_neighbors) {
-                            (false, 3) | (true, 2..=3) => true,
-                            _ => false,
-                        };
+                        let next_state = rule.next_state(self_state, alive_neighbors);
                         result[i][j] = next_state;
                     }
                 }
-                MacroCell::Leaf(MacroCellLeaf { states: result })
+                MacroCell::Leaf(MacroCellLeaf::new(result))
             },
             |branches: [[&CachedMacroCellBranch; 2]; 2]| -> MacroCell {
-                fn horizontal_shift_result(
+                let n = self.level();
+                assert!(step_pow <= n - 2, "step_pow must be at most level - 2");
+
+                fn horizontal_shift_branch(
                     left: &CachedMacroCellBranch,
                     right: &CachedMacroCellBranch,
-                    cache: &mut Cache,
-                ) -> MacroCell {
-                    let quadrants = [
-                        [left.branches[0][1].clone(), right.branches[0][0].clone()],
-                        [left.branches[1][1].clone(), right.branches[1][0].clone()],
-                    ];
-                    let (_, result) = CachedMacroCellBranch::new_result(
-                        MacroCellBranch {
-                            branches: quadrants,
-                        },
-                        cache,
-                    );
-                    result
+                ) -> MacroCellBranch {
+                    MacroCellBranch {
+                        branches: [
+                            [left.branches[0][1].clone(), right.branches[0][0].clone()],
+                            [left.branches[1][1].clone(), right.branches[1][0].clone()],
+                        ],
+                    }
                 }
-                fn vertical_shift_result(
+                fn vertical_shift_branch(
                     top: &CachedMacroCellBranch,
                     bottom: &CachedMacroCellBranch,
-                    cache: &mut Cache,
-                ) -> MacroCell {
-                    let quadrants = [
-                        [top.branches[1][0].clone(), top.branches[1][1].clone()],
-                        [bottom.branches[0][0].clone(), bottom.branches[0][1].clone()],
-                    ];
-                    let (_, result) = CachedMacroCellBranch::new_result(
-                        MacroCellBranch {
-                            branches: quadrants,
-                        },
-                        cache,
-                    );
-                    result
+                ) -> MacroCellBranch {
+                    MacroCellBranch {
+                        branches: [
+                            [top.branches[1][0].clone(), top.branches[1][1].clone()],
+                            [bottom.branches[0][0].clone(), bottom.branches[0][1].clone()],
+                        ],
+                    }
                 }
-                fn corner_shift_result(
-                    quadrants: [[&CachedMacroCellBranch; 2]; 2],
-                    cache: &mut Cache,
-                ) -> MacroCell {
-                    let corner_quadrants = [
+                fn corner_shift_branch(quadrants: [[&CachedMacroCellBranch; 2]; 2]) -> MacroCellBranch {
+                    MacroCellBranch {
+                        branches: [
+                            [
+                                quadrants[0][0].branches[1][1].clone(),
+                                quadrants[0][1].branches[1][0].clone(),
+                            ],
+                            [
+                                quadrants[1][0].branches[0][1].clone(),
+                                quadrants[1][1].branches[0][0].clone(),
+                            ],
+                        ],
+                    }
+                }
+
+                if step_pow == n - 2 {
+                    // Maximum jump: pull the quarter-advanced result of each of the
+                    // nine overlapping subnodes, then combine them into four
+                    // overlapping nodes and advance each of those once more.
+                    let shifted_results: [[MacroCell; 3]; 3] = [
+                        [
+                            branches[0][0].result(cache, step_pow - 1),
+                            CachedMacroCellBranch::new_result(
+                                horizontal_shift_branch(branches[0][0], branches[0][1]),
+                                step_pow - 1,
+                                cache,
+                            )
+                            .1,
+                            branches[0][1].result(cache, step_pow - 1),
+                        ],
                         [
-                            quadrants[0][0].branches[1][1].clone(),
-                            quadrants[0][1].branches[1][0].clone(),
+                            CachedMacroCellBranch::new_result(
+                                vertical_shift_branch(branches[0][0], branches[1][0]),
+                                step_pow - 1,
+                                cache,
+                            )
+                            .1,
+                            CachedMacroCellBranch::new_result(
+                                corner_shift_branch(branches),
+                                step_pow - 1,
+                                cache,
+                            )
+                            .1,
+                            CachedMacroCellBranch::new_result(
+                                vertical_shift_branch(branches[0][1], branches[1][1]),
+                                step_pow - 1,
+                                cache,
+                            )
+                            .1,
                         ],
                         [
-                            quadrants[1][0].branches[0][1].clone(),
-                            quadrants[1][1].branches[0][0].clone(),
+                            branches[1][0].result(cache, step_pow - 1),
+                            CachedMacroCellBranch::new_result(
+                                horizontal_shift_branch(branches[1][0], branches[1][1]),
+                                step_pow - 1,
+                                cache,
+                            )
+                            .1,
+                            branches[1][1].result(cache, step_pow - 1),
                         ],
                     ];
-                    let (_, result) = CachedMacroCellBranch::new_result(
+
+                    let mut get_overlap_result = |i: usize, j: usize| -> MacroCell {
+                        let quadrants = [
+                            [
+                                shifted_results[i][j].clone(),
+                                shifted_results[i][j + 1].clone(),
+                            ],
+                            [
+                                shifted_results[i + 1][j].clone(),
+                                shifted_results[i + 1][j + 1].clone(),
+                            ],
+                        ];
+                        CachedMacroCellBranch::new_result(
+                            MacroCellBranch {
+                                branches: quadrants,
+                            },
+                            step_pow - 1,
+                            cache,
+                        )
+                        .1
+                    };
+                    let overlapping_quadrants_results: [[MacroCell; 2]; 2] = [
+                        [get_overlap_result(0, 0), get_overlap_result(0, 1)],
+                        [get_overlap_result(1, 0), get_overlap_result(1, 1)],
+                    ];
+
+                    let (branch, _) = CachedMacroCellBranch::new_result(
                         MacroCellBranch {
-                            branches: corner_quadrants,
+                            branches: overlapping_quadrants_results,
                         },
+                        step_pow - 1,
                         cache,
                     );
-                    result
-                }
-
-                let shifted_results: [[MacroCell; 3]; 3] = [
-                    [
-                        branches[0][0].result(cache),
-                        horizontal_shift_result(&branches[0][0], &branches[0][1], cache),
-                        branches[0][1].result(cache),
-                    ],
-                    [
-                        vertical_shift_result(&branches[0][0], &branches[1][0], cache),
-                        corner_shift_result(branches, cache),
-                        vertical_shift_result(&branches[0][1], &branches[1][1], cache),
-                    ],
-                    [
-                        branches[1][0].result(cache),
-                        horizontal_shift_result(&branches[1][0], &branches[1][1], cache),
-                        branches[1][1].result(cache),
-                    ],
-                ];
+                    MacroCell::Branch(branch)
+                } else {
+                    // Slower than the maximum jump: extract the nine center
+                    // subnodes without advancing them (the same shifted quadrants
+                    // as above, but left at level n-1 instead of being advanced
+                    // down to n-2). Recurse at the same step_pow on each of the
+                    // nine -- valid since step_pow <= n-3 == (n-1)-2 -- then crop
+                    // the four overlapping corners of the nine results back down
+                    // to the final quadrants, without advancing any further.
+                    let shifted_nodes: [[CachedMacroCellBranch; 3]; 3] = [
+                        [
+                            branches[0][0].clone(),
+                            CachedMacroCellBranch::intern(
+                                horizontal_shift_branch(branches[0][0], branches[0][1]),
+                                cache,
+                            ),
+                            branches[0][1].clone(),
+                        ],
+                        [
+                            CachedMacroCellBranch::intern(
+                                vertical_shift_branch(branches[0][0], branches[1][0]),
+                                cache,
+                            ),
+                            CachedMacroCellBranch::intern(corner_shift_branch(branches), cache),
+                            CachedMacroCellBranch::intern(
+                                vertical_shift_branch(branches[0][1], branches[1][1]),
+                                cache,
+                            ),
+                        ],
+                        [
+                            branches[1][0].clone(),
+                            CachedMacroCellBranch::intern(
+                                horizontal_shift_branch(branches[1][0], branches[1][1]),
+                                cache,
+                            ),
+                            branches[1][1].clone(),
+                        ],
+                    ];
 
-                let mut get_overlap_result = |i: usize, j: usize| -> MacroCell {
-                    let quadrants = [
+                    let shifted_results: [[MacroCell; 3]; 3] = [
+                        [
+                            shifted_nodes[0][0].result(cache, step_pow),
+                            shifted_nodes[0][1].result(cache, step_pow),
+                            shifted_nodes[0][2].result(cache, step_pow),
+                        ],
                         [
-                            shifted_results[i][j].clone(),
-                            shifted_results[i][j + 1].clone(),
+                            shifted_nodes[1][0].result(cache, step_pow),
+                            shifted_nodes[1][1].result(cache, step_pow),
+                            shifted_nodes[1][2].result(cache, step_pow),
                         ],
                         [
-                            shifted_results[i + 1][j].clone(),
-                            shifted_results[i + 1][j + 1].clone(),
+                            shifted_nodes[2][0].result(cache, step_pow),
+                            shifted_nodes[2][1].result(cache, step_pow),
+                            shifted_nodes[2][2].result(cache, step_pow),
                         ],
                     ];
-                    let (_, result) = CachedMacroCellBranch::new_result(
+
+                    let mut get_overlap_result = |i: usize, j: usize| -> MacroCell {
+                        crop_quadrant(
+                            &shifted_results[i][j],
+                            &shifted_results[i][j + 1],
+                            &shifted_results[i + 1][j],
+                            &shifted_results[i + 1][j + 1],
+                            cache,
+                        )
+                    };
+                    let overlapping_quadrants_results: [[MacroCell; 2]; 2] = [
+                        [get_overlap_result(0, 0), get_overlap_result(0, 1)],
+                        [get_overlap_result(1, 0), get_overlap_result(1, 1)],
+                    ];
+
+                    let (branch, _) = CachedMacroCellBranch::new_result(
                         MacroCellBranch {
-                            branches: quadrants,
+                            branches: overlapping_quadrants_results,
                         },
+                        step_pow,
                         cache,
                     );
-                    result
-                };
-                let overlapping_quadrants_results: [[MacroCell; 2]; 2] = [
-                    [get_overlap_result(0, 0), get_overlap_result(0, 1)],
-                    [get_overlap_result(1, 0), get_overlap_result(1, 1)],
-                ];
-
-                let (branch, _) = CachedMacroCellBranch::new_result(
-                    MacroCellBranch {
-                        branches: overlapping_quadrants_results,
-                    },
-                    cache,
-                );
-                MacroCell::Branch(branch)
+                    MacroCell::Branch(branch)
+                }
             },
         )
     }
@@ -350,15 +486,18 @@ impl MacroCellBranch {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rule::Rule;
+    use crate::state_buffer::{parse_plaintext, parse_rle, to_rle};
 
     fn assert_result(world_grid: &str, expected_grid: &str) {
-        let mut cache = Cache::new();
+        let mut cache = Cache::new(Rule::default());
 
         let world_buf = parse_plaintext(world_grid);
         let expected_buf = parse_plaintext(expected_grid);
 
         let world = MacroCell::from_square(world_buf.view(), &mut cache);
-        let result = world.result(&cache).unwrap();
+        let step_pow = world.level() - 2;
+        let result = world.result(&mut cache, step_pow).unwrap();
 
         let expected = MacroCell::from_square(expected_buf.view(), &mut cache);
 
@@ -486,4 +625,130 @@ mod tests {
             ",
         )
     }
+
+    #[test]
+    fn rle_round_trip_through_macro_cell() {
+        let rle = "\
+            #C glider\n\
+            x = 4, y = 4, rule = B3/S23\n\
+            bob$2bo$3o$4b!\n\
+        ";
+        let buf = parse_rle(rle);
+        let mut cache = Cache::new(Rule::default());
+        let world = MacroCell::from_square(buf.view(), &mut cache);
+
+        let expected_buf = parse_plaintext(
+            "\
+                .O..\n\
+                ..O.\n\
+                OOO.\n\
+                ....\n\
+            ",
+        );
+        assert_eq!(world.to_state_buffer().view().rows(), expected_buf.view().rows());
+        for row in 0..4 {
+            for col in 0..4 {
+                assert_eq!(
+                    world.to_state_buffer().view()[(row, col)],
+                    expected_buf.view()[(row, col)]
+                );
+            }
+        }
+
+        let rle_out = to_rle(world.to_state_buffer().view());
+        let reparsed = parse_rle(&rle_out);
+        for row in 0..4 {
+            for col in 0..4 {
+                assert_eq!(reparsed.view()[(row, col)], expected_buf.view()[(row, col)]);
+            }
+        }
+    }
+
+    /// Crops `node` (a branch) down to its center quadrant, one level down,
+    /// without advancing -- the same operation `compute_result`'s slow path
+    /// uses to combine its nine shifted subresults.
+    fn center_quadrant(node: &MacroCell, cache: &mut Cache) -> MacroCell {
+        match node {
+            MacroCell::Leaf(..) => panic!("a leaf has no center quadrant"),
+            MacroCell::Branch(branch) => crop_quadrant(
+                &branch.branches[0][0],
+                &branch.branches[0][1],
+                &branch.branches[1][0],
+                &branch.branches[1][1],
+                cache,
+            ),
+        }
+    }
+
+    #[test]
+    fn slow_step_pow_matches_repeated_single_steps() {
+        let mut cache = Cache::new(Rule::default());
+        let world_buf = parse_plaintext(
+            "\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                .........OO.....\n\
+                ........O.O.....\n\
+                ........OO......\n\
+                ......OO........\n\
+                .....O.O........\n\
+                .....OO.........\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+                ................\n\
+            ",
+        );
+        let world = MacroCell::from_square(world_buf.view(), &mut cache);
+        assert_eq!(world.level(), 4);
+
+        // world.level() - 2 == 2 is the max jump; pick something slower.
+        let step_pow = 1;
+        assert!(step_pow < world.level() - 2);
+
+        // One jump of 2^step_pow generations, as a level-(n-1) node.
+        let fast = world.result(&mut cache, step_pow).unwrap();
+
+        // The same 2^step_pow generations, taken as single-generation steps;
+        // each step also shrinks the node by one level.
+        let mut slow = world.clone();
+        for _ in 0..(1u32 << step_pow) {
+            slow = slow.result(&mut cache, 0).unwrap();
+        }
+
+        // `fast` is one level taller than `slow` since a single `result()`
+        // call only ever lowers the level by one, regardless of step_pow;
+        // crop it down to the same center region before comparing.
+        let fast_center = center_quadrant(&fast, &mut cache);
+        assert_eq!(fast_center, slow);
+    }
+
+    #[test]
+    fn identical_patterns_are_hash_consed() {
+        let mut cache = Cache::new(Rule::default());
+
+        let grid = "\
+            ....\n\
+            .OO.\n\
+            .OO.\n\
+            ....\n\
+        ";
+        let buf_a = parse_plaintext(grid);
+        let buf_b = parse_plaintext(grid);
+
+        // Two independently-built `MacroCell`s for the same pattern, sharing
+        // one `Cache`, should intern to the exact same branch. `CachedMacroCellBranch`'s
+        // `PartialEq` compares by `Rc` pointer identity (not structural
+        // equality), so `assert_eq!` here is itself the pointer-equality
+        // check, and `cache.len() == 1` confirms only one branch was interned.
+        let a = MacroCell::from_square(buf_a.view(), &mut cache);
+        let b = MacroCell::from_square(buf_b.view(), &mut cache);
+        assert!(matches!(a, MacroCell::Branch(_)));
+        assert_eq!(a, b);
+        assert_eq!(cache.len(), 1);
+    }
 }
\ No newline at end of file
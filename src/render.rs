@@ -0,0 +1,178 @@
+use crate::state_buffer::StateBufferView;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOpts {
+    pub alive_glyph: char,
+    pub dead_glyph: char,
+    pub frame: bool,
+    pub axes: bool,
+}
+
+impl Default for RenderOpts {
+    fn default() -> Self {
+        Self {
+            alive_glyph: 'O',
+            dead_glyph: '.',
+            frame: true,
+            axes: false,
+        }
+    }
+}
+
+/// Renders `view` as a character grid, one line per row. With `opts.frame`
+/// the grid is enclosed in a `+`/`-`/`|` border line above and below; with
+/// `opts.axes` a column ruler line comes first and each row is prefixed with
+/// its index.
+pub fn render_region(view: StateBufferView, opts: RenderOpts) -> String {
+    let rows = view.rows();
+    let cols = view.cols();
+    let row_label_width = if opts.axes {
+        rows.saturating_sub(1).to_string().len()
+    } else {
+        0
+    };
+
+    let mut out = String::new();
+
+    if opts.axes {
+        out.push_str(&" ".repeat(row_label_width));
+        if opts.frame {
+            out.push(' ');
+        }
+        for col in 0..cols {
+            out.push(std::char::from_digit((col % 10) as u32, 10).unwrap());
+        }
+        out.push('\n');
+    }
+
+    if opts.frame {
+        out.push_str(&" ".repeat(row_label_width));
+        out.push('+');
+        out.push_str(&"-".repeat(cols));
+        out.push_str("+\n");
+    }
+
+    for row in 0..rows {
+        if opts.axes {
+            out.push_str(&format!("{:>width$}", row, width = row_label_width));
+        }
+        if opts.frame {
+            out.push('|');
+        }
+        for col in 0..cols {
+            out.push(if view[(row, col)] {
+                opts.alive_glyph
+            } else {
+                opts.dead_glyph
+            });
+        }
+        if opts.frame {
+            out.push('|');
+        }
+        out.push('\n');
+    }
+
+    if opts.frame {
+        out.push_str(&" ".repeat(row_label_width));
+        out.push('+');
+        out.push_str(&"-".repeat(cols));
+        out.push_str("+\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_buffer::parse_plaintext;
+
+    #[test]
+    fn renders_with_frame() {
+        let buf = parse_plaintext(
+            "\
+                .O.\n\
+                ..O\n\
+                OOO\n\
+            ",
+        );
+        let rendered = render_region(buf.view(), RenderOpts::default());
+        assert_eq!(
+            rendered,
+            "\
+                +---+\n\
+                |.O.|\n\
+                |..O|\n\
+                |OOO|\n\
+                +---+\n\
+            "
+        );
+    }
+
+    #[test]
+    fn renders_sub_rectangle_without_frame() {
+        let buf = parse_plaintext(
+            "\
+                ....\n\
+                .OO.\n\
+                .OO.\n\
+                ....\n\
+            ",
+        );
+        let view = buf.view().sub_rectangle(1..3, 1..3);
+        let rendered = render_region(
+            view,
+            RenderOpts {
+                frame: false,
+                ..RenderOpts::default()
+            },
+        );
+        assert_eq!(rendered, "OO\nOO\n");
+    }
+
+    #[test]
+    fn renders_axes_with_frame() {
+        let buf = parse_plaintext(
+            "\
+                .O.\n\
+                ..O\n\
+                OOO\n\
+            ",
+        );
+        let rendered = render_region(
+            buf.view(),
+            RenderOpts {
+                axes: true,
+                ..RenderOpts::default()
+            },
+        );
+        // The backslash-continued string literals used elsewhere in this file
+        // strip all leading whitespace on each continued line, which would
+        // swallow the axes' leading alignment spaces -- so this expected
+        // value is written as a single literal instead.
+        assert_eq!(
+            rendered,
+            "  012\n +---+\n0|.O.|\n1|..O|\n2|OOO|\n +---+\n"
+        );
+    }
+
+    #[test]
+    fn renders_axes_without_frame() {
+        let buf = parse_plaintext(
+            "\
+                .O.\n\
+                ..O\n\
+                OOO\n\
+            ",
+        );
+        let rendered = render_region(
+            buf.view(),
+            RenderOpts {
+                axes: true,
+                frame: false,
+                ..RenderOpts::default()
+            },
+        );
+        assert_eq!(rendered, " 012\n0.O.\n1..O\n2OOO\n");
+    }
+}
@@ -0,0 +1,87 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    pub fn new(birth: [bool; 9], survival: [bool; 9]) -> Self {
+        Self { birth, survival }
+    }
+
+    /// Parses the standard `"B3/S23"` notation: a count is included in the
+    /// birth (`B`) set if a dead cell with that many live neighbors becomes
+    /// alive, and in the survival (`S`) set if a live cell with that many
+    /// live neighbors stays alive.
+    pub fn parse(s: &str) -> Self {
+        let (birth, survival) = s
+            .split_once('/')
+            .unwrap_or_else(|| panic!("expected rule of the form \"B.../S...\", got {:?}", s));
+        Self {
+            birth: Self::parse_counts(birth, 'B'),
+            survival: Self::parse_counts(survival, 'S'),
+        }
+    }
+
+    fn parse_counts(part: &str, prefix: char) -> [bool; 9] {
+        let digits = part
+            .strip_prefix(prefix)
+            .unwrap_or_else(|| panic!("expected {:?} to start with {:?}", part, prefix));
+        let mut counts = [false; 9];
+        for c in digits.chars() {
+            let n = c
+                .to_digit(10)
+                .unwrap_or_else(|| panic!("invalid neighbor count {:?}", c)) as usize;
+            if n > 8 {
+                panic!("neighbor count {:?} out of range 0-8", c);
+            }
+            counts[n] = true;
+        }
+        counts
+    }
+
+    pub fn next_state(&self, alive: bool, live_neighbors: usize) -> bool {
+        if alive {
+            self.survival[live_neighbors]
+        } else {
+            self.birth[live_neighbors]
+        }
+    }
+}
+
+impl Default for Rule {
+    /// Conway's standard rule, `B3/S23`.
+    fn default() -> Self {
+        Self::parse("B3/S23")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conway() {
+        let rule = Rule::parse("B3/S23");
+        assert_eq!(rule, Rule::default());
+        assert!(rule.next_state(false, 3));
+        assert!(!rule.next_state(false, 2));
+        assert!(rule.next_state(true, 2));
+        assert!(rule.next_state(true, 3));
+        assert!(!rule.next_state(true, 4));
+        assert!(!rule.next_state(true, 1));
+    }
+
+    #[test]
+    fn parses_highlife() {
+        let rule = Rule::parse("B36/S23");
+        assert!(rule.next_state(false, 6));
+        assert!(!rule.next_state(false, 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range 0-8")]
+    fn rejects_out_of_range_neighbor_count() {
+        Rule::parse("B9/S23");
+    }
+}
@@ -87,10 +87,116 @@ impl<'a, const ROWS: usize, const COLS: usize> From<&'a [[bool; ROWS]; COLS]>
     for StateBufferView<'a>
 {
     fn from(value: &'a [[bool; ROWS]; COLS]) -> Self {
-        Self::new(value.flatten(), ROWS, COLS)
+        Self::new((value as &[[bool; ROWS]]).as_flattened(), ROWS, COLS)
     }
 }
 
+/// Parses the standard Run-Length-Encoded Life format: `#`-comment lines,
+/// an `x = .., y = .., rule = ..` header (the `rule` field, if present, is
+/// recognized but ignored), then a body of `<count><tag>` runs where `tag`
+/// is `o` (alive), `b` (dead), `$` (end of row, with `count` meaning that
+/// many consecutive end-of-rows), ending with `!`. A missing count means 1.
+pub fn parse_rle(s: &str) -> StateBuffer {
+    let mut lines = s.lines().filter(|line| !line.starts_with('#'));
+    let header = lines
+        .next()
+        .unwrap_or_else(|| panic!("missing RLE header line"));
+    let (cols, rows) = parse_rle_header(header);
+
+    let mut buf = vec![false; rows * cols];
+    let mut row = 0;
+    let mut col = 0;
+    let mut count = String::new();
+
+    'outer: for line in lines {
+        for c in line.chars() {
+            match c {
+                '0'..='9' => count.push(c),
+                'b' | 'o' | '$' => {
+                    let n: usize = if count.is_empty() {
+                        1
+                    } else {
+                        count.parse().unwrap()
+                    };
+                    count.clear();
+                    if c == '$' {
+                        row += n;
+                        col = 0;
+                    } else {
+                        let state = c == 'o';
+                        for _ in 0..n {
+                            buf[row * cols + col] = state;
+                            col += 1;
+                        }
+                    }
+                }
+                '!' => break 'outer,
+                c if c.is_whitespace() => {}
+                _ => panic!("unexpected char {:?} in RLE body", c),
+            }
+        }
+    }
+
+    StateBuffer::new(buf, rows, cols)
+}
+
+fn parse_rle_header(line: &str) -> (usize, usize) {
+    let mut cols = None;
+    let mut rows = None;
+    for field in line.split(',') {
+        let field = field.trim();
+        if let Some(value) = field.strip_prefix("x =").or_else(|| field.strip_prefix("x=")) {
+            cols = Some(
+                value
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid x in RLE header {:?}", line)),
+            );
+        } else if let Some(value) = field.strip_prefix("y =").or_else(|| field.strip_prefix("y=")) {
+            rows = Some(
+                value
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid y in RLE header {:?}", line)),
+            );
+        }
+    }
+    (
+        cols.unwrap_or_else(|| panic!("missing \"x =\" in RLE header {:?}", line)),
+        rows.unwrap_or_else(|| panic!("missing \"y =\" in RLE header {:?}", line)),
+    )
+}
+
+/// Writes `view` out in the standard Run-Length-Encoded Life format. Always
+/// encodes every cell explicitly (including trailing dead runs), so it
+/// round-trips through [`parse_rle`] without relying on RLE's optional
+/// trailing-dead-cell elision.
+pub fn to_rle(view: StateBufferView) -> String {
+    let mut out = format!("x = {}, y = {}\n", view.cols(), view.rows());
+
+    for row in 0..view.rows() {
+        let mut col = 0;
+        while col < view.cols() {
+            let state = view[(row, col)];
+            let start = col;
+            while col < view.cols() && view[(row, col)] == state {
+                col += 1;
+            }
+            let run = col - start;
+            let tag = if state { 'o' } else { 'b' };
+            if run == 1 {
+                out.push(tag);
+            } else {
+                out.push_str(&run.to_string());
+                out.push(tag);
+            }
+        }
+        out.push(if row + 1 < view.rows() { '$' } else { '!' });
+    }
+    out.push('\n');
+    out
+}
+
 pub fn parse_plaintext(s: &str) -> StateBuffer {
     let lines = s.lines().filter(|line| !line.starts_with('!'));
     let rows = lines.clone().count();
@@ -110,3 +216,68 @@ pub fn parse_plaintext(s: &str) -> StateBuffer {
 
     StateBuffer::new(buf, rows, cols)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_multi_digit_run_counts() {
+        let buf = parse_rle("x = 12, y = 1\n12o!\n");
+        let view = buf.view();
+        assert_eq!(view.rows(), 1);
+        assert_eq!(view.cols(), 12);
+        for col in 0..12 {
+            assert!(view[(0, col)]);
+        }
+    }
+
+    #[test]
+    fn parses_multi_row_dollar_gaps() {
+        // "3$" after the first row's run skips two fully-blank rows, landing
+        // on row 3.
+        let buf = parse_rle("x = 1, y = 4\no3$o!\n");
+        let view = buf.view();
+        assert!(view[(0, 0)]);
+        assert!(!view[(1, 0)]);
+        assert!(!view[(2, 0)]);
+        assert!(view[(3, 0)]);
+    }
+
+    #[test]
+    fn skips_multiple_comment_lines() {
+        let buf = parse_rle("#C first comment\n#C second comment\nx = 2, y = 1\nbo!\n");
+        let view = buf.view();
+        assert!(!view[(0, 0)]);
+        assert!(view[(0, 1)]);
+    }
+
+    #[test]
+    fn ignores_rule_field_in_header() {
+        let buf = parse_rle("x = 2, y = 1, rule = B3/S23\nob!\n");
+        let view = buf.view();
+        assert!(view[(0, 0)]);
+        assert!(!view[(0, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing RLE header line")]
+    fn rejects_empty_input() {
+        parse_rle("");
+    }
+
+    #[test]
+    #[should_panic(expected = "missing \"x =\" in RLE header")]
+    fn rejects_malformed_header() {
+        parse_rle("not a header\no!\n");
+    }
+
+    #[test]
+    fn round_trips_multi_digit_runs_through_to_rle() {
+        let buf = parse_rle("x = 12, y = 1\n12o!\n");
+        let reparsed = parse_rle(&to_rle(buf.view()));
+        for col in 0..12 {
+            assert_eq!(reparsed.view()[(0, col)], buf.view()[(0, col)]);
+        }
+    }
+}